@@ -1,125 +1,594 @@
-use crate::graph::NodeType::{BinaryB2nd, BinaryFn, Param, UnaryFn};
-use std::cell::RefCell;
-use std::rc::{Rc, Weak};
-
-pub struct Node {
-    inputs: Vec<Rc<RefCell<Node>>>,
-    outputs: Vec<Weak<RefCell<Node>>>,
-    cache: Option<f32>,
+use crate::graph::NodeType::{BinaryB2nd, BinaryFn, NaryClosure, Param, UnaryClosure, UnaryFn};
+#[cfg(not(feature = "parallel"))]
+use std::cell::{RefCell, RefMut};
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::ops::{Add, Mul};
+#[cfg(not(feature = "parallel"))]
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "parallel")]
+use std::sync::{Arc, Mutex, MutexGuard};
+use serde::{Deserialize, Serialize};
+
+// global monotonic clock: bumped on every `set`, so a node's stamped
+// generation tells us "as of which set() am I up to date" without ever
+// having to walk downstream to invalidate anyone.
+static GENERATION: AtomicU64 = AtomicU64::new(1);
+
+fn next_generation() -> u64 {
+    GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Element type usable as a node's value: just the arithmetic the built-in
+/// `add`/`mul` ops need. Only the `parallel` feature additionally requires
+/// `Send + Sync + 'static`, since that's what lets `Node<T>` live behind an
+/// `Arc<Mutex<_>>` and cross a `rayon::join` boundary; the default,
+/// single-threaded build has no such requirement.
+#[cfg(not(feature = "parallel"))]
+pub trait Scalar: Copy + Add<Output = Self> + Mul<Output = Self> {}
+#[cfg(not(feature = "parallel"))]
+impl<T: Copy + Add<Output = T> + Mul<Output = T>> Scalar for T {}
+
+#[cfg(feature = "parallel")]
+pub trait Scalar: Copy + Add<Output = Self> + Mul<Output = Self> + Send + Sync + 'static {}
+#[cfg(feature = "parallel")]
+impl<T: Copy + Add<Output = T> + Mul<Output = T> + Send + Sync + 'static> Scalar for T {}
+
+/// Scalars that also support the float-only ops `sin`/`pow_f32` need.
+pub trait Float: Scalar {
+    fn sin(self) -> Self;
+    fn powf(self, n: Self) -> Self;
+}
+
+impl Float for f32 {
+    fn sin(self) -> Self {
+        f32::sin(self)
+    }
+
+    fn powf(self, n: Self) -> Self {
+        f32::powf(self, n)
+    }
+}
+
+impl Float for f64 {
+    fn sin(self) -> Self {
+        f64::sin(self)
+    }
+
+    fn powf(self, n: Self) -> Self {
+        f64::powf(self, n)
+    }
+}
+
+pub struct Node<T> {
+    inputs: Vec<ShNode<T>>,
+    cache: Option<T>,
+    generation: u64,
     name: String,
-    node_t: NodeType,
+    node_t: NodeType<T>,
+    // memoized result of `input_names`: the graph's structure never changes
+    // after construction, so this is computed once and reused on every
+    // `compute_parallel` scheduling decision instead of re-walked each time.
+    #[cfg(feature = "parallel")]
+    input_names_cache: Option<HashSet<String>>,
+}
+
+// the `parallel` feature swaps the single-threaded Rc<RefCell<_>> handle for
+// an Arc<Mutex<_>> one so that independent subtrees can be evaluated on a
+// rayon thread pool; the default build stays allocation- and lock-free.
+#[cfg(not(feature = "parallel"))]
+pub type ShNode<T> = Rc<RefCell<Node<T>>>;
+#[cfg(feature = "parallel")]
+pub type ShNode<T> = Arc<Mutex<Node<T>>>;
+
+#[cfg(not(feature = "parallel"))]
+fn wrap<T>(node: Node<T>) -> ShNode<T> {
+    Rc::new(RefCell::new(node))
+}
+#[cfg(feature = "parallel")]
+fn wrap<T>(node: Node<T>) -> ShNode<T> {
+    Arc::new(Mutex::new(node))
+}
+
+#[cfg(not(feature = "parallel"))]
+fn lock_mut<T>(node: &ShNode<T>) -> RefMut<'_, Node<T>> {
+    node.borrow_mut()
+}
+#[cfg(feature = "parallel")]
+fn lock_mut<T>(node: &ShNode<T>) -> MutexGuard<'_, Node<T>> {
+    node.lock().unwrap()
+}
+
+// a stable, comparable identity for a node, independent of which handle type
+// backs `ShNode` under the current feature set; used to key visited-sets and
+// adjoint maps by node rather than by value.
+#[cfg(not(feature = "parallel"))]
+type NodePtr<T> = *const RefCell<Node<T>>;
+#[cfg(feature = "parallel")]
+type NodePtr<T> = *const Mutex<Node<T>>;
+
+#[cfg(not(feature = "parallel"))]
+fn node_ptr<T>(node: &ShNode<T>) -> NodePtr<T> {
+    Rc::as_ptr(node)
+}
+#[cfg(feature = "parallel")]
+fn node_ptr<T>(node: &ShNode<T>) -> NodePtr<T> {
+    Arc::as_ptr(node)
 }
 
-pub type ShNode = Rc<RefCell<Node>>;
+#[cfg(not(feature = "parallel"))]
+type UnaryClosureFn<T> = Box<dyn Fn(T) -> T>;
+#[cfg(not(feature = "parallel"))]
+type NaryClosureFn<T> = Box<dyn Fn(&[T]) -> T>;
+#[cfg(feature = "parallel")]
+type UnaryClosureFn<T> = Box<dyn Fn(T) -> T + Send + Sync>;
+#[cfg(feature = "parallel")]
+type NaryClosureFn<T> = Box<dyn Fn(&[T]) -> T + Send + Sync>;
 
-enum NodeType {
+enum NodeType<T> {
     Param,
-    UnaryFn(fn(f32) -> f32),
-    BinaryFn(fn(f32, f32) -> f32),
-    BinaryB2nd(fn(f32, f32) -> f32, f32),
+    UnaryFn(fn(T) -> T),
+    BinaryFn(fn(T, T) -> T),
+    BinaryB2nd(fn(T, T) -> T, T),
+    // closure-backed variants: costs an allocation per node, but lets a node
+    // capture runtime data (coefficients, lookup tables, config) instead of
+    // requiring a new named `fn` op for every use case. Only bounded by
+    // `Send + Sync` under the `parallel` feature, which is what actually
+    // needs it to cross a `rayon::join` boundary; the default build doesn't
+    // require captured state (including other `ShNode`s) to be thread-safe.
+    UnaryClosure(UnaryClosureFn<T>),
+    NaryClosure(NaryClosureFn<T>),
 }
 
-pub trait Setter {
-    fn set(&self, value: f32);
+pub trait Setter<T> {
+    fn set(&self, value: T);
 }
 
-impl Setter for ShNode {
-    fn set(&self, value: f32) {
-        self.borrow_mut().set(value);
+impl<T: Scalar> Setter<T> for ShNode<T> {
+    fn set(&self, value: T) {
+        lock_mut(self).set(value);
     }
 }
 
-pub trait Computer {
-    fn compute(&self) -> f32;
+pub trait Computer<T> {
+    fn compute(&self) -> T;
 }
 
-impl Computer for ShNode {
-    fn compute(&self) -> f32 {
-        self.borrow_mut().compute()
+impl<T: Scalar> Computer<T> for ShNode<T> {
+    fn compute(&self) -> T {
+        lock_mut(self).compute()
     }
 }
 
-impl Node {
-    fn new(name: &str, node_t: NodeType, inputs: Vec<ShNode>) -> ShNode {
-        let node = Rc::new(RefCell::new(Node {
+impl<T: Scalar> Node<T> {
+    fn new(name: &str, node_t: NodeType<T>, inputs: Vec<ShNode<T>>) -> ShNode<T> {
+        wrap(Node {
             inputs,
-            outputs: Vec::new(),
             cache: None,
+            generation: 0,
             name: name.to_string(),
             node_t,
-        }));
+            #[cfg(feature = "parallel")]
+            input_names_cache: None,
+        })
+    }
 
-        for input in node.borrow().inputs.iter() {
-            input.borrow_mut().outputs.push(Rc::downgrade(&node));
+    fn set(&mut self, value: T) {
+        if let Param = self.node_t {
+            self.generation = next_generation();
+            self.cache = Some(value);
         }
-
-        node
     }
 
-    fn set(&mut self, value: f32) {
+    fn compute(&mut self) -> T {
         if let Param = self.node_t {
-            self.invalidate();
-            self.cache = Some(value);
+            // notes: call unwrap_or_else instead of .expect for lazy string format creation
+            return self
+                .cache
+                .unwrap_or_else(|| panic!("Please set value for input {}", self.name));
         }
+
+        // a node's generation is only meaningful once every input has been
+        // asked for its own current generation; shared/diamond inputs are
+        // handled for free since we're reading, not pushing, the value.
+        let mut max_gen = 0u64;
+        let input_values: Vec<T> = self
+            .inputs
+            .iter()
+            .map(|input| {
+                let mut input = lock_mut(input);
+                let value = input.compute();
+                max_gen = max_gen.max(input.generation);
+                value
+            })
+            .collect();
+
+        if self.cache.is_none() || max_gen > self.generation {
+            self.cache = Some(self.apply(&input_values));
+            self.generation = max_gen;
+        }
+
+        self.cache.unwrap()
     }
 
-    fn compute(&mut self) -> f32 {
-        if let Some(cache) = self.cache {
-            cache
-        } else {
-            let new_value = match self.node_t {
-                // notes: call unwrap_or_else instead of .expect for lazy string format creation
-                Param => self
-                    .cache
-                    .unwrap_or_else(|| panic!("Please set value for input {}", self.name)),
-                UnaryFn(f) => f(self.inputs[0].borrow_mut().compute()),
-                BinaryFn(f) => {
-                    let x1 = self.inputs[0].borrow_mut().compute();
-                    let x2 = self.inputs[1].borrow_mut().compute();
-                    f(x1, x2)
-                }
-                BinaryB2nd(f, x) => f(self.inputs[0].borrow_mut().compute(), x),
-            };
-            *self.cache.insert(new_value)
+    fn apply(&self, input_values: &[T]) -> T {
+        match &self.node_t {
+            Param => unreachable!(),
+            UnaryFn(f) => f(input_values[0]),
+            BinaryFn(f) => f(input_values[0], input_values[1]),
+            BinaryB2nd(f, x) => f(input_values[0], *x),
+            UnaryClosure(f) => f(input_values[0]),
+            NaryClosure(f) => f(input_values),
         }
     }
+}
+
+/// Evaluate independent input subtrees concurrently on a rayon thread pool.
+/// Only available when the `parallel` cargo feature is enabled, which also
+/// switches `ShNode` from `Rc<RefCell<_>>` to `Arc<Mutex<_>>`.
+#[cfg(feature = "parallel")]
+pub trait ParallelComputer<T> {
+    fn compute_parallel(&self) -> T;
+}
+
+#[cfg(feature = "parallel")]
+impl<T: Scalar> ParallelComputer<T> for ShNode<T> {
+    fn compute_parallel(&self) -> T {
+        lock_mut(self).compute_parallel().0
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<T: Scalar> Node<T> {
+    // returns (value, generation) so a caller already holding this node's
+    // lock can read its up-to-date generation without re-locking.
+    fn compute_parallel(&mut self) -> (T, u64) {
+        if let Param = self.node_t {
+            let value = self
+                .cache
+                .unwrap_or_else(|| panic!("Please set value for input {}", self.name));
+            return (value, self.generation);
+        }
+
+        // only two disjoint inputs are worth a rayon::join: anything sharing
+        // a leaf (e.g. the `add(x1, x1)` case) would just fight over its lock.
+        let can_split = self.inputs.len() == 2
+            && input_names(&self.inputs[0]).is_disjoint(&input_names(&self.inputs[1]));
+
+        let results: Vec<(T, u64)> = if can_split {
+            let (left, right) = rayon::join(
+                || lock_mut(&self.inputs[0]).compute_parallel(),
+                || lock_mut(&self.inputs[1]).compute_parallel(),
+            );
+            vec![left, right]
+        } else {
+            self.inputs
+                .iter()
+                .map(|input| lock_mut(input).compute_parallel())
+                .collect()
+        };
 
-    // recursively invalidate all output nodes
-    fn invalidate(&mut self) {
-        self.cache = None;
+        let max_gen = results.iter().map(|&(_, gen)| gen).max().unwrap_or(0);
+        let input_values: Vec<T> = results.into_iter().map(|(value, _)| value).collect();
 
-        for node in self.outputs.iter_mut() {
-            node.upgrade().map(|n| n.borrow_mut().invalidate());
+        if self.cache.is_none() || max_gen > self.generation {
+            self.cache = Some(self.apply(&input_values));
+            self.generation = max_gen;
         }
+
+        (self.cache.unwrap(), self.generation)
+    }
+}
+
+// the set of Param names feeding a node, used to decide whether two input
+// subtrees are independent enough to evaluate on separate threads. Memoized
+// on the node itself, since the graph's structure is fixed after
+// construction: without this, the walk is redone at every level of
+// recursion for the same sub-subtrees, making it quadratic in graph size.
+#[cfg(feature = "parallel")]
+fn input_names<T: Scalar>(node: &ShNode<T>) -> HashSet<String> {
+    if let Some(names) = &lock_mut(node).input_names_cache {
+        return names.clone();
     }
+
+    let is_param = matches!(lock_mut(node).node_t, Param);
+    let names = if is_param {
+        let mut names = HashSet::new();
+        names.insert(lock_mut(node).name.clone());
+        names
+    } else {
+        let inputs = lock_mut(node).inputs.clone();
+        inputs.iter().fold(HashSet::new(), |mut names, input| {
+            names.extend(input_names(input));
+            names
+        })
+    };
+
+    lock_mut(node).input_names_cache = Some(names.clone());
+    names
 }
 
-pub fn create_input(name: &str) -> ShNode {
+pub fn create_input<T: Scalar>(name: &str) -> ShNode<T> {
     Node::new(name, Param, Vec::new())
 }
 
-pub fn add(input1: ShNode, input2: ShNode) -> ShNode {
+pub fn add<T: Scalar>(input1: ShNode<T>, input2: ShNode<T>) -> ShNode<T> {
     Node::new("add", BinaryFn(|x, y| x + y), vec![input1, input2])
 }
 
-pub fn mul(input1: ShNode, input2: ShNode) -> ShNode {
+pub fn mul<T: Scalar>(input1: ShNode<T>, input2: ShNode<T>) -> ShNode<T> {
     Node::new("mul", BinaryFn(|x, y| x * y), vec![input1, input2])
 }
 
-pub fn pow_f32(input: ShNode, n: f32) -> ShNode {
+pub fn pow_f32<T: Float>(input: ShNode<T>, n: T) -> ShNode<T> {
     Node::new("pow", BinaryB2nd(|x, y| x.powf(y), n), vec![input])
 }
 
-pub fn sin(input: ShNode) -> ShNode {
+pub fn sin<T: Float>(input: ShNode<T>) -> ShNode<T> {
     Node::new("sin", UnaryFn(|x| x.sin()), vec![input])
 }
 
+/// Apply an arbitrary closure to a single node, e.g. `map(x.clone(), move |v| v * scale)`.
+#[cfg(not(feature = "parallel"))]
+pub fn map<T: Scalar>(input: ShNode<T>, f: impl Fn(T) -> T + 'static) -> ShNode<T> {
+    Node::new("map", NodeType::UnaryClosure(Box::new(f)), vec![input])
+}
+#[cfg(feature = "parallel")]
+pub fn map<T: Scalar>(input: ShNode<T>, f: impl Fn(T) -> T + Send + Sync + 'static) -> ShNode<T> {
+    Node::new("map", NodeType::UnaryClosure(Box::new(f)), vec![input])
+}
+
+/// Combine two nodes with an arbitrary closure.
+#[cfg(not(feature = "parallel"))]
+pub fn zip2<T: Scalar>(
+    input1: ShNode<T>,
+    input2: ShNode<T>,
+    f: impl Fn(T, T) -> T + 'static,
+) -> ShNode<T> {
+    Node::new(
+        "zip2",
+        NodeType::NaryClosure(Box::new(move |values: &[T]| f(values[0], values[1]))),
+        vec![input1, input2],
+    )
+}
+#[cfg(feature = "parallel")]
+pub fn zip2<T: Scalar>(
+    input1: ShNode<T>,
+    input2: ShNode<T>,
+    f: impl Fn(T, T) -> T + Send + Sync + 'static,
+) -> ShNode<T> {
+    Node::new(
+        "zip2",
+        NodeType::NaryClosure(Box::new(move |values: &[T]| f(values[0], values[1]))),
+        vec![input1, input2],
+    )
+}
+
+/// Generalizes `add`/`mul` to arbitrary-arity fan-in via an arbitrary closure.
+#[cfg(not(feature = "parallel"))]
+pub fn reduce<T: Scalar>(inputs: Vec<ShNode<T>>, f: impl Fn(&[T]) -> T + 'static) -> ShNode<T> {
+    Node::new("reduce", NodeType::NaryClosure(Box::new(f)), inputs)
+}
+#[cfg(feature = "parallel")]
+pub fn reduce<T: Scalar>(
+    inputs: Vec<ShNode<T>>,
+    f: impl Fn(&[T]) -> T + Send + Sync + 'static,
+) -> ShNode<T> {
+    Node::new("reduce", NodeType::NaryClosure(Box::new(f)), inputs)
+}
+
+// kept concretely `f32`-only, like the serde subsystem below, rather than
+// generic over `Scalar`/`Float`: the adjoint arithmetic has no need to
+// support anything but the crate's original precision. Identity is keyed by
+// `node_ptr` rather than `Rc::as_ptr` directly, so this stays available
+// regardless of whether the `parallel` feature is swapping `ShNode` to
+// `Arc<Mutex<_>>` underneath it.
+pub trait Gradient {
+    /// Partial derivatives of this (output) node w.r.t. every named input,
+    /// computed by reverse-mode AD over the values already cached by the
+    /// preceding `compute()`. Panics if an input of a reachable `mul`, `sin`
+    /// or `pow` node holds no cached value (params and `add` nodes are not
+    /// checked), or if a `map`/`zip2`/`reduce` closure node lies on the path back to a
+    /// param — those have no known derivative rule, so `grad()` refuses to
+    /// silently return an incomplete result.
+    fn grad(&self) -> HashMap<String, f32>;
+}
+
+impl Gradient for ShNode<f32> {
+    fn grad(&self) -> HashMap<String, f32> {
+        // leaves-first order so that, read in reverse, every node is visited
+        // only once all of its downstream adjoints have already accumulated.
+        let order = topo_order(self);
+
+        let mut adjoints: HashMap<NodePtr<f32>, f32> = HashMap::new();
+        adjoints.insert(node_ptr(self), 1.0);
+
+        let mut result = HashMap::new();
+
+        for node_rc in order.into_iter().rev() {
+            let adj = match adjoints.get(&node_ptr(&node_rc)) {
+                Some(&adj) => adj,
+                None => continue,
+            };
+            let node = lock_mut(&node_rc);
+
+            if let Param = node.node_t {
+                *result.entry(node.name.clone()).or_insert(0.0) += adj;
+                continue;
+            }
+
+            let cached = |input: &ShNode<f32>| {
+                lock_mut(input)
+                    .cache
+                    .unwrap_or_else(|| panic!("grad() requires compute() to have run first"))
+            };
+
+            match node.name.as_str() {
+                "add" => {
+                    for input in &node.inputs {
+                        *adjoints.entry(node_ptr(input)).or_insert(0.0) += adj;
+                    }
+                }
+                "mul" => {
+                    let x = cached(&node.inputs[0]);
+                    let y = cached(&node.inputs[1]);
+                    *adjoints.entry(node_ptr(&node.inputs[0])).or_insert(0.0) += adj * y;
+                    *adjoints.entry(node_ptr(&node.inputs[1])).or_insert(0.0) += adj * x;
+                }
+                "sin" => {
+                    let x = cached(&node.inputs[0]);
+                    *adjoints.entry(node_ptr(&node.inputs[0])).or_insert(0.0) += adj * x.cos();
+                }
+                "pow" => {
+                    if let BinaryB2nd(_, n) = node.node_t {
+                        let x = cached(&node.inputs[0]);
+                        *adjoints.entry(node_ptr(&node.inputs[0])).or_insert(0.0) +=
+                            adj * n * x.powf(n - 1.0);
+                    }
+                }
+                // closures (see `map`/`zip2`/`reduce`) have no known derivative
+                // rule; silently dropping their adjoint would make `grad()`
+                // return incomplete results for any param feeding one, so
+                // fail loudly here instead.
+                other => panic!(
+                    "grad() has no derivative rule for closure-backed node \"{other}\"; \
+                     map/zip2/reduce nodes are not differentiable"
+                ),
+            }
+        }
+
+        result
+    }
+}
+
+// post-order DFS over `inputs`: every node appears after all of its own
+// inputs, so nodes earlier in the list never depend on nodes later in it.
+fn topo_order(root: &ShNode<f32>) -> Vec<ShNode<f32>> {
+    let mut visited: HashSet<NodePtr<f32>> = HashSet::new();
+    let mut order = Vec::new();
+    visit(root, &mut visited, &mut order);
+    return order;
+
+    fn visit(node: &ShNode<f32>, visited: &mut HashSet<NodePtr<f32>>, order: &mut Vec<ShNode<f32>>) {
+        let ptr = node_ptr(node);
+        if !visited.insert(ptr) {
+            return;
+        }
+        let inputs = lock_mut(node).inputs.clone();
+        for input in &inputs {
+            visit(input, visited, order);
+        }
+        order.push(node.clone());
+    }
+}
+
+// a flat, cycle-free stand-in for the graph: edges are indices into `nodes`
+// instead of `Rc`/`Arc` pointers, so the whole thing round-trips through
+// serde. Kept concretely `f32`-only for the same reason `Gradient` is above,
+// but not scoped to the default build: identity is keyed by `node_ptr` and
+// field access goes through `lock_mut`, so this works under both `ShNode`
+// backings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeRecord {
+    name: String,
+    op: String,
+    param: Option<f32>,
+    inputs: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphDescriptor {
+    nodes: Vec<NodeRecord>,
+    root: usize,
+}
+
+#[derive(Debug)]
+pub enum GraphLoadError {
+    UnknownOp(String),
+}
+
+impl std::fmt::Display for GraphLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphLoadError::UnknownOp(op) => write!(f, "unknown node op tag: {op}"),
+        }
+    }
+}
+
+impl std::error::Error for GraphLoadError {}
+
+/// Walk the graph from its output node and flatten it into a `GraphDescriptor`.
+pub fn save(root: &ShNode<f32>) -> GraphDescriptor {
+    let mut index_of: HashMap<NodePtr<f32>, usize> = HashMap::new();
+    let mut nodes = Vec::new();
+    let root_index = visit(root, &mut index_of, &mut nodes);
+    return GraphDescriptor { nodes, root: root_index };
+
+    fn visit(
+        node: &ShNode<f32>,
+        index_of: &mut HashMap<NodePtr<f32>, usize>,
+        nodes: &mut Vec<NodeRecord>,
+    ) -> usize {
+        let ptr = node_ptr(node);
+        if let Some(&index) = index_of.get(&ptr) {
+            return index;
+        }
+
+        let inputs = lock_mut(node).inputs.clone();
+        let input_indices: Vec<usize> = inputs.iter().map(|input| visit(input, index_of, nodes)).collect();
+
+        let n = lock_mut(node);
+        let (op, param) = match &n.node_t {
+            Param => ("param".to_string(), None),
+            BinaryB2nd(_, x) => (n.name.clone(), Some(*x)),
+            UnaryFn(_) | BinaryFn(_) | UnaryClosure(_) | NaryClosure(_) => (n.name.clone(), None),
+        };
+
+        let record = NodeRecord {
+            name: n.name.clone(),
+            op,
+            param,
+            inputs: input_indices,
+        };
+        drop(n);
+
+        let index = nodes.len();
+        nodes.push(record);
+        index_of.insert(ptr, index);
+        index
+    }
+}
+
+/// Reconstruct a graph from a `GraphDescriptor`, rewiring edges back into `ShNode`s.
+/// Errors cleanly if the descriptor references an op tag we don't know how to
+/// rebuild (e.g. a `map`/`zip2`/`reduce` closure, which can't be serialized).
+pub fn load(desc: &GraphDescriptor) -> Result<ShNode<f32>, GraphLoadError> {
+    let mut built: Vec<ShNode<f32>> = Vec::with_capacity(desc.nodes.len());
+
+    for record in &desc.nodes {
+        let inputs: Vec<ShNode<f32>> = record.inputs.iter().map(|&i| built[i].clone()).collect();
+        let node = match record.op.as_str() {
+            "param" => create_input(&record.name),
+            "add" => add(inputs[0].clone(), inputs[1].clone()),
+            "mul" => mul(inputs[0].clone(), inputs[1].clone()),
+            "sin" => sin(inputs[0].clone()),
+            "pow" => pow_f32(inputs[0].clone(), record.param.unwrap_or(0.0)),
+            other => return Err(GraphLoadError::UnknownOp(other.to_string())),
+        };
+        built.push(node);
+    }
+
+    Ok(built[desc.root].clone())
+}
 
-#[cfg(test)]
+
+#[cfg(all(test, not(feature = "parallel")))]
 mod tests {
     use std::f32::consts::{FRAC_PI_2};
     use std::rc::Rc;
-    use crate::{add, Computer, create_input, mul, pow_f32, Setter, sin};
+    use crate::graph::{load, save};
+    use crate::{add, Computer, create_input, Gradient, map, mul, pow_f32, reduce, Setter, sin, zip2};
 
     fn round(x: f32, precision: u32) -> f32 {
         let m = 10i32.pow(precision) as f32;
@@ -219,6 +688,227 @@ mod tests {
         // check if node fully dropped
         assert_eq!(weak.strong_count(), 0);
     }
+
+    #[test]
+    fn stale_branch_is_not_recomputed() {
+        // x2 feeds only the right branch; touching x1 must not force a
+        // recompute of the right branch, since generations are read from
+        // inputs rather than pushed through the whole downstream graph.
+        let x1 = create_input("x1");
+        let x2 = create_input("x2");
+        x1.set(1.0);
+        x2.set(10.0);
+
+        let right = mul(x2.clone(), x2.clone());
+        assert_eq!(right.compute(), 100.0);
+        let right_generation_after_first_compute = right.borrow().generation;
+
+        x1.set(2.0);
+        let graph = add(x1.clone(), right.clone());
+        assert_eq!(graph.compute(), 102.0);
+
+        // right's cached value/generation are untouched by x1's set()
+        assert_eq!(right.borrow().generation, right_generation_after_first_compute);
+    }
+
+    #[test]
+    fn grad_mul() {
+        // z = x * y => dz/dx = y, dz/dy = x
+        let x = create_input("x");
+        let y = create_input("y");
+        x.set(3.0);
+        y.set(4.0);
+
+        let graph = mul(x, y);
+        graph.compute();
+        let grads = graph.grad();
+
+        assert_eq!(grads["x"], 4.0);
+        assert_eq!(grads["y"], 3.0);
+    }
+
+    #[test]
+    fn grad_diamond() {
+        // z = x + x => dz/dx = 2, adjoints must accumulate across both edges
+        let x = create_input("x");
+        x.set(5.0);
+
+        let graph = add(x.clone(), x);
+        graph.compute();
+        let grads = graph.grad();
+
+        assert_eq!(grads["x"], 2.0);
+    }
+
+    #[test]
+    fn grad_sin_pow() {
+        // z = sin(x) ^ 2 => dz/dx = 2 * sin(x) * cos(x)
+        let x = create_input("x");
+        x.set(FRAC_PI_2 / 2.0);
+
+        let graph = pow_f32(sin(x.clone()), 2.0);
+        graph.compute();
+        let grads = graph.grad();
+
+        let expected = 2.0 * (FRAC_PI_2 / 2.0).sin() * (FRAC_PI_2 / 2.0).cos();
+        assert_eq!(round(grads["x"], 5), round(expected, 5));
+    }
+
+    #[test]
+    #[should_panic(expected = "no derivative rule")]
+    fn grad_panics_on_closure_node() {
+        // map/zip2/reduce have no known derivative rule, so grad() must
+        // refuse to silently return an incomplete result for a param behind one.
+        let x = create_input("x");
+        x.set(3.0);
+
+        let graph = map(x, |v| v * 2.0);
+        graph.compute();
+        graph.grad();
+    }
+
+    #[test]
+    fn map_closure() {
+        let x = create_input("x");
+        x.set(3.0);
+
+        let scale = 2.0;
+        let graph = map(x.clone(), move |v| v * scale);
+        assert_eq!(graph.compute(), 6.0);
+
+        x.set(4.0);
+        assert_eq!(graph.compute(), 8.0);
+    }
+
+    #[test]
+    fn zip2_closure() {
+        let x: crate::graph::ShNode<f32> = create_input("x");
+        let y = create_input("y");
+        x.set(3.0);
+        y.set(4.0);
+
+        let graph = zip2(x, y, |a, b| a.hypot(b));
+        assert_eq!(graph.compute(), 5.0);
+    }
+
+    #[test]
+    fn reduce_closure() {
+        let x1 = create_input("x1");
+        let x2 = create_input("x2");
+        let x3 = create_input("x3");
+        x1.set(1.0);
+        x2.set(2.0);
+        x3.set(3.0);
+
+        let graph = reduce(vec![x1, x2, x3], |values| values.iter().sum());
+        assert_eq!(graph.compute(), 6.0);
+    }
+
+    #[test]
+    fn save_load_round_trip() {
+        let x1 = create_input("x1");
+        let x2 = create_input("x2");
+        let x3 = create_input("x3");
+        let graph = add(
+            x1.clone(),
+            mul(x2.clone(), sin(add(x2.clone(), pow_f32(x3.clone(), 3f32)))),
+        );
+
+        let desc = save(&graph);
+        let serialized = serde_json::to_string(&desc).expect("descriptor should serialize");
+        let deserialized = serde_json::from_str(&serialized).expect("descriptor should deserialize");
+        let reloaded = load(&deserialized).expect("descriptor should reconstruct");
+
+        x1.set(1f32);
+        x2.set(2f32);
+        x3.set(3f32);
+        let original = round(graph.compute(), 5);
+
+        reloaded_set(&reloaded, "x1", 1f32);
+        reloaded_set(&reloaded, "x2", 2f32);
+        reloaded_set(&reloaded, "x3", 3f32);
+        let from_reload = round(reloaded.compute(), 5);
+
+        assert_eq!(original, from_reload);
+    }
+
+    #[test]
+    fn load_rejects_unknown_op() {
+        let x = create_input("x");
+        let desc = save(&map(x, |v| v * 2.0));
+        let result = load(&desc);
+        assert!(result.is_err());
+    }
+
+    // `load` rebuilds fresh Param nodes, so tests that want to drive a
+    // reloaded graph need to find the right one by name rather than by
+    // holding on to the original `ShNode` handles.
+    fn reloaded_set(root: &crate::graph::ShNode<f32>, name: &str, value: f32) {
+        fn find(node: &crate::graph::ShNode<f32>, name: &str) -> Option<crate::graph::ShNode<f32>> {
+            if node.borrow().name == name {
+                return Some(node.clone());
+            }
+            node.borrow()
+                .inputs
+                .iter()
+                .find_map(|input| find(input, name))
+        }
+
+        find(root, name)
+            .unwrap_or_else(|| panic!("no node named {name}"))
+            .set(value);
+    }
+
+    #[test]
+    fn generic_f64_graph() {
+        // the same constructors, instantiated at f64 instead of f32, to show
+        // `Node<T>` isn't hard-coded to single precision.
+        let x: crate::graph::ShNode<f64> = create_input("x");
+        x.set(3.0);
+
+        let graph = pow_f32(sin(x.clone()), 2.0);
+        let result = graph.compute();
+
+        let expected = x.compute().sin().powf(2.0);
+        assert_eq!(result, expected);
+    }
 }
 
+#[cfg(all(test, feature = "parallel"))]
+mod parallel_tests {
+    use crate::graph::ParallelComputer;
+    use crate::{add, create_input, mul, pow_f32, sin, Computer, Setter};
+
+    fn round(x: f32, precision: u32) -> f32 {
+        let m = 10i32.pow(precision) as f32;
+        (x * m).round() / m
+    }
+
+    #[test]
+    fn compute_parallel_matches_compute() {
+        let x1 = create_input("x1");
+        let x2 = create_input("x2");
+        let x3 = create_input("x3");
+        x1.set(1f32);
+        x2.set(2f32);
+        x3.set(3f32);
+
+        // disjoint branches: safe to evaluate x1 and the mul/sin/pow branch concurrently
+        let graph = add(
+            x1.clone(),
+            mul(x2.clone(), sin(add(x2.clone(), pow_f32(x3.clone(), 3f32)))),
+        );
+
+        assert_eq!(round(graph.compute_parallel(), 5), round(graph.compute(), 5));
+    }
 
+    #[test]
+    fn compute_parallel_handles_shared_leaf() {
+        // add(x1, x1) shares a single leaf, so the scheduler must fall back
+        // to sequential evaluation instead of racing on x1's lock.
+        let x1 = create_input("x1");
+        x1.set(4.0);
+        let graph = add(x1.clone(), x1.clone());
+        assert_eq!(graph.compute_parallel(), 8.0);
+    }
+}
@@ -1,5 +1,9 @@
 mod graph;
-use crate::graph::{add, create_input, mul, pow_f32, sin, Computer, Setter};
+use crate::graph::{add, create_input, map, mul, pow_f32, reduce, sin, zip2, Computer, Setter};
+use crate::graph::Gradient;
+use crate::graph::{load, save};
+#[cfg(feature = "parallel")]
+use crate::graph::ParallelComputer;
 
 // round to decimal digits
 fn round(x: f32, precision: u32) -> f32 {
@@ -33,4 +37,44 @@ fn main() {
     result = round(result, 5);
     println!("Graph output = {}", result);
     assert_eq!(round(result, 5), -0.56656);
+
+    // reverse-mode AD: partial derivatives of `graph` w.r.t. each named input.
+    let grads = graph.grad();
+    println!(
+        "d/dx1 = {}, d/dx2 = {}, d/dx3 = {}",
+        grads["x1"], grads["x2"], grads["x3"]
+    );
+
+    // parallel evaluation: same graph, but independent input subtrees are
+    // computed on a rayon thread pool instead of sequentially.
+    #[cfg(feature = "parallel")]
+    {
+        let parallel_result = graph.compute_parallel();
+        println!("Graph output (parallel) = {}", round(parallel_result, 5));
+    }
+
+    // serialization: flatten the graph to a cycle-free descriptor and back
+    let desc = save(&graph);
+    let json = serde_json::to_string(&desc).expect("descriptor should serialize");
+    println!("Serialized graph = {}", json);
+    load(&desc).expect("descriptor should reconstruct");
+
+    // closure-backed nodes: capture runtime data without a new named op
+    let scale = 2f32;
+    let scaled = map(x1.clone(), move |v| v * scale);
+    let combined = zip2(x1.clone(), x2.clone(), |a, b| a.hypot(b));
+    let total = reduce(vec![x1.clone(), x2.clone(), x3.clone()], |values| values.iter().sum());
+    println!(
+        "scaled = {}, combined = {}, total = {}",
+        scaled.compute(),
+        combined.compute(),
+        total.compute()
+    );
+
+    // `Node` is generic over the scalar type: the same constructors work at
+    // f64 precision for deep graphs where f32 accumulation error matters.
+    let y: crate::graph::ShNode<f64> = create_input("y");
+    y.set(2.0);
+    let precise = pow_f32(sin(y), 2.0);
+    println!("precise (f64) = {}", precise.compute());
 }